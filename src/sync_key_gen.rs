@@ -4,7 +4,10 @@
 //! messages in the same order. It can e.g. exchange messages as transactions on top of
 //! `HoneyBadger`, or it can run "on-chain", i.e. committing its messages to a blockchain.
 //!
-//! Its messages are encrypted where necessary, so they can be publicly broadcast.
+//! Its messages are encrypted where necessary, so they can be publicly broadcast. `Propose` and
+//! `Accept` also carry the sender's signature, so that a node forwarding them — a gossip network,
+//! an on-chain relay, an observer re-broadcasting what it saw — cannot be mistaken for their
+//! origin.
 //!
 //! When the protocol completes, every node receives a secret key share suitable for threshold
 //! signatures and encryption. The secret master key is not known by anyone. The protocol succeeds
@@ -79,11 +82,12 @@
 //!     proposals.push((id, opt_proposal.unwrap())); // Would be `None` for observer nodes.
 //! }
 //!
-//! // All nodes now handle the proposals and send the resulting `Accept` messages.
+//! // All nodes now handle the proposals and send the resulting `Accept` messages. Since `Propose`
+//! // and `Accept` carry the sender's signature, no separate sender identity needs to be passed in.
 //! let mut accepts = Vec::new();
-//! for (sender_id, proposal) in proposals {
+//! for (_, proposal) in proposals {
 //!     for (&id, node) in &mut nodes {
-//!         match node.handle_propose(&sender_id, proposal.clone()) {
+//!         match node.handle_propose(proposal.clone()) {
 //!             Some(ProposeOutcome::Valid(accept)) => accepts.push((id, accept)),
 //!             Some(ProposeOutcome::Invalid(faults)) => panic!("Invalid proposal: {:?}", faults),
 //!             None => panic!("We are not an observer, so we should send Accept."),
@@ -92,9 +96,9 @@
 //! }
 //!
 //! // Finally, we handle all the `Accept`s.
-//! for (sender_id, accept) in accepts {
+//! for (_, accept) in accepts {
 //!     for node in nodes.values_mut() {
-//!         node.handle_accept(&sender_id, accept.clone());
+//!         node.handle_accept(accept.clone());
 //!     }
 //! }
 //!
@@ -150,9 +154,31 @@
 //! In our _dealerless_ environment, at least _t + 1_ nodes each generate a polynomial using the
 //! method above. The sum of the secret keys we received from each node is then used as our secret
 //! key. No single node knows the secret master key.
+//!
+//! ## Gossip-based agreement
+//!
+//! `SyncKeyGen` itself still requires every node to handle the same `Propose`/`Accept` messages in
+//! the same order, e.g. by committing them to a blockchain first. `DkgState` removes that
+//! requirement: it wraps a `SyncKeyGen` and additionally has nodes broadcast an `AllAcks` message
+//! once enough proposals are locally complete, listing the exact set of proposals and accepts they
+//! used. As soon as `2 * threshold + 1` nodes have endorsed the same, lexicographically-first
+//! `AllAcks` set, every node calls `generate` restricted to that set, so a plain authenticated
+//! point-to-point (gossip) network is enough; no external total order is needed.
+//!
+//! ## Resharing
+//!
+//! `SyncKeyGen::new_reshare` hands an existing threshold key, held by one validator set, over to a
+//! new (possibly overlapping) set and threshold, while keeping the same master public key: current
+//! share holders contribute a polynomial pinned to their own share instead of a random one, and
+//! `generate` recombines those contributions weighted by their Lagrange coefficients over the old
+//! validator set's indices — exactly as reconstructing the old secret directly from those shares
+//! would — so the new set's shares back the very same secret. This is how membership churn — nodes
+//! joining or leaving — can be handled without invalidating signatures made under the old master
+//! key. Because indices can shift across such churn, callers pass `new_reshare` an explicit map
+//! from each old share holder to its old index, rather than relying on it matching their new one.
 
 use std::collections::btree_map::Entry;
-use std::collections::{BTreeMap, BTreeSet};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
 use std::fmt::{self, Debug, Formatter};
 
 use bincode;
@@ -162,7 +188,10 @@ use rand::OsRng;
 
 use crypto::poly::{BivarCommitment, BivarPoly, Poly};
 use crypto::serde_impl::field_vec::FieldWrap;
-use crypto::{Ciphertext, PublicKey, PublicKeySet, SecretKey, SecretKeyShare};
+use crypto::{
+    Ciphertext, DecryptionProof, PublicKey, PublicKeySet, PublicKeyShare, SecretKey,
+    SecretKeyShare, Signature,
+};
 use fault_log::{FaultKind, FaultLog};
 
 // TODO: No need to send our own row and value to ourselves.
@@ -198,10 +227,110 @@ impl Debug for Accept {
     }
 }
 
+/// Proof that a proposer sent us a row that does not match its public commitment.
+///
+/// The row was encrypted to the complainant alone, so revealing the plaintext `row` is not enough
+/// by itself: anyone could falsely accuse an honest proposer by just making up a row that fails
+/// the commitment check. `proof` binds `row` to the exact `ciphertext` the proposer actually sent
+/// (found in the proposer's `Propose`), so every node — not just the complainant — can verify
+/// whether the proposer or the complainant is the one at fault.
+#[derive(Deserialize, Serialize, Clone, Hash, Eq, PartialEq)]
+pub struct Complaint {
+    /// The index of the proposer whose row is being disputed.
+    proposer_idx: u64,
+    /// The index of the node the disputed row was encrypted to.
+    complainant_idx: u64,
+    /// The ciphertext the proposer sent to the complainant, copied from its `Propose`.
+    ciphertext: Ciphertext,
+    /// The plaintext row the complainant decrypted from `ciphertext`.
+    row: Poly,
+    /// Proof that `row` is indeed what `ciphertext` decrypts to under the complainant's public
+    /// key.
+    proof: DecryptionProof,
+}
+
+impl Debug for Complaint {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Complaint {{ proposer_idx: {}, complainant_idx: {}, .. }}",
+            self.proposer_idx, self.complainant_idx
+        )
+    }
+}
+
+/// A proposer's response to a `Complaint` against one of its rows, revealing the plaintext row it
+/// actually sent so every node can check directly whether the row matches the public commitment.
+#[derive(Deserialize, Serialize, Clone, Hash, Eq, PartialEq)]
+pub struct Justification {
+    /// The index of the proposer that is justifying its row.
+    proposer_idx: u64,
+    /// The index of the node the row was originally sent to.
+    complainant_idx: u64,
+    /// The plaintext row the proposer claims to have sent.
+    row: Poly,
+}
+
+impl Debug for Justification {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Justification {{ proposer_idx: {}, complainant_idx: {}, .. }}",
+            self.proposer_idx, self.complainant_idx
+        )
+    }
+}
+
+/// A `Propose` or `Accept` payload together with the index and signature of the node that
+/// produced it. Unlike the bare payload, a `SignedMsg` authenticates its own origin, so it can be
+/// safely forwarded by an untrusted third party (see the module docs) without the recipient having
+/// to trust whoever forwarded it: the signature is checked against the claimed sender's regular,
+/// non-threshold public key.
+#[derive(Deserialize, Serialize, Clone, Hash, Eq, PartialEq)]
+pub struct SignedMsg<T> {
+    sender_idx: u64,
+    payload: T,
+    sig: Signature,
+}
+
+impl<T: Debug> Debug for SignedMsg<T> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "SignedMsg {{ sender_idx: {}, payload: {:?}, .. }}",
+            self.sender_idx, self.payload
+        )
+    }
+}
+
+impl<T: Serialize> SignedMsg<T> {
+    /// Signs `payload` on behalf of `sender_idx`, using `sec_key`.
+    fn new(sender_idx: u64, payload: T, sec_key: &SecretKey) -> Self {
+        let ser = bincode::serialize(&payload).expect("failed to serialize payload");
+        let sig = sec_key.sign(ser);
+        SignedMsg {
+            sender_idx,
+            payload,
+            sig,
+        }
+    }
+
+    /// Returns `true` if `sig` is a valid signature of `payload` by `pub_key`.
+    fn is_valid(&self, pub_key: &PublicKey) -> bool {
+        match bincode::serialize(&self.payload) {
+            Ok(ser) => pub_key.verify(&self.sig, ser),
+            Err(_) => false,
+        }
+    }
+}
+
 /// The information needed to track a single proposer's secret sharing process.
 struct ProposalState {
     /// The proposer's commitment.
     commit: BivarCommitment,
+    /// The encrypted rows from the original `Propose`, kept so that a later `Complaint` about one
+    /// of them can be checked against exactly what the proposer sent.
+    rows: Vec<Ciphertext>,
     /// The verified values we received from `Accept` messages.
     values: BTreeMap<u64, Fr>,
     /// The nodes which have accepted this proposal, valid or not.
@@ -209,10 +338,11 @@ struct ProposalState {
 }
 
 impl ProposalState {
-    /// Creates a new proposal state with a commitment.
-    fn new(commit: BivarCommitment) -> ProposalState {
+    /// Creates a new proposal state with a commitment and the rows sent for it.
+    fn new(commit: BivarCommitment, rows: Vec<Ciphertext>) -> ProposalState {
         ProposalState {
             commit,
+            rows,
             values: BTreeMap::new(),
             accepts: BTreeSet::new(),
         }
@@ -227,13 +357,17 @@ impl ProposalState {
 /// The outcome of handling and verifying a `Propose` message.
 pub enum ProposeOutcome<NodeUid: Clone> {
     /// The message was valid: the part of it that was encrypted to us matched the public
-    /// commitment, so we can multicast an `Accept` message for it.
-    Valid(Accept),
+    /// commitment, so we can multicast a signed `Accept` message for it.
+    Valid(SignedMsg<Accept>),
     // If the Propose message passed to `handle_propose()` is invalid, the
     // fault is logged and passed onto the caller.
     /// The message was invalid: the part encrypted to us was malformed or didn't match the
     /// commitment. We now know that the proposer is faulty, and dont' send an `Accept`.
     Invalid(FaultLog<NodeUid>),
+    /// The part encrypted to us didn't match the commitment, but — unlike `Invalid` — this is
+    /// provable to everyone else: broadcast the `Complaint` so all nodes can independently verify
+    /// it and disqualify the proposer.
+    Complaint(Complaint),
 }
 
 /// A synchronous algorithm for dealerless distributed key generation.
@@ -250,6 +384,26 @@ pub struct SyncKeyGen<NodeUid> {
     proposals: BTreeMap<u64, ProposalState>,
     /// The degree of the generated polynomial.
     threshold: usize,
+    /// Our own proposal's polynomial, kept so we can justify our row if a `Complaint` is raised
+    /// against us. `None` if we are an observer and proposed nothing.
+    our_proposal: Option<BivarPoly>,
+    /// Proposers that a verified `Complaint` has shown to be faulty. Their proposals are excluded
+    /// from `generate`, regardless of how many `Accept`s they received.
+    disqualified: BTreeSet<u64>,
+    /// The number of complete, non-disqualified proposals required for `is_ready`. This is
+    /// `threshold` for an ordinary key generation, but the *old* key's threshold when resharing
+    /// (see `new_reshare`), since that many old share holders must contribute correctly to
+    /// reconstruct the same master secret under the new membership.
+    quorum: usize,
+    /// If this is a resharing of an existing key, the public key set of the key being reshared.
+    /// Used to verify that a proposer's polynomial really commits to its own old secret key share.
+    old_pub_key_set: Option<PublicKeySet>,
+    /// If this is a resharing, every old share holder's index under the *old* validator set, by
+    /// `NodeUid`. Membership can change across a reshare — nodes can join or leave, and a
+    /// `BTreeMap`'s iteration order shifts around any such change — so a proposer's index in
+    /// `pub_keys` (the new set) cannot be assumed to equal its old index; this is the explicit
+    /// mapping between the two that `handle_propose` and `generate_for` need instead.
+    old_indices: Option<BTreeMap<NodeUid, u64>>,
 }
 
 impl<NodeUid: Ord + Clone + Debug> SyncKeyGen<NodeUid> {
@@ -263,21 +417,27 @@ impl<NodeUid: Ord + Clone + Debug> SyncKeyGen<NodeUid> {
         sec_key: SecretKey,
         pub_keys: BTreeMap<NodeUid, PublicKey>,
         threshold: usize,
-    ) -> (SyncKeyGen<NodeUid>, Option<Propose>) {
+    ) -> (SyncKeyGen<NodeUid>, Option<SignedMsg<Propose>>) {
         let our_idx = pub_keys
             .keys()
             .position(|uid| uid == our_uid)
             .map(|idx| idx as u64);
-        let key_gen = SyncKeyGen {
+        let mut key_gen = SyncKeyGen {
             our_idx,
             sec_key,
             pub_keys,
             proposals: BTreeMap::new(),
             threshold,
+            our_proposal: None,
+            disqualified: BTreeSet::new(),
+            quorum: threshold,
+            old_pub_key_set: None,
+            old_indices: None,
+        };
+        let our_idx = match our_idx {
+            Some(our_idx) => our_idx,
+            None => return (key_gen, None), // No proposal: we are an observer.
         };
-        if our_idx.is_none() {
-            return (key_gen, None); // No proposal: we are an observer.
-        }
         let mut rng = OsRng::new().expect("OS random number generator");
         let our_proposal = BivarPoly::random(threshold, &mut rng);
         let commit = our_proposal.commitment();
@@ -287,40 +447,143 @@ impl<NodeUid: Ord + Clone + Debug> SyncKeyGen<NodeUid> {
             pk.encrypt(&bytes)
         };
         let rows: Vec<_> = key_gen.pub_keys.values().enumerate().map(encrypt).collect();
-        (key_gen, Some(Propose(commit, rows)))
+        let propose = SignedMsg::new(our_idx, Propose(commit, rows), &key_gen.sec_key);
+        key_gen.our_proposal = Some(our_proposal);
+        (key_gen, Some(propose))
+    }
+
+    /// Creates a new `SyncKeyGen` instance that reshares an existing threshold key to a new
+    /// (possibly overlapping) validator set `new_pub_keys` with a new `new_threshold`, keeping the
+    /// same master public key. Returns the instance together with the `Propose` message that
+    /// should be multicast to all new participants.
+    ///
+    /// `our_uid` and `sec_key` identify us among `new_pub_keys`. `old_pub_key_set` is the public
+    /// key set of the key being reshared, and `old_sk_share` is our share of it, if we held one —
+    /// `None` if we are joining without ever having been part of the old set, in which case we
+    /// produce no `Propose`, but still handle the others' proposals, send `Accept`s and receive a
+    /// new share if we are in `new_pub_keys`. `old_indices`
+    /// maps every old share holder's `NodeUid` to its index under the *old* validator set: since
+    /// membership can change across a reshare, a proposer's index in `new_pub_keys` is not
+    /// necessarily its old index, and both `handle_propose` and `generate` need the real one.
+    ///
+    /// Instead of sampling a polynomial with a random constant term, a current share holder
+    /// samples one whose value at `0` equals its own secret key share. `generate` then recovers
+    /// the master secret by summing these contributions weighted by their Lagrange coefficients
+    /// over the old indices used — not by a plain sum, which would reconstruct an arbitrary value
+    /// instead of the original secret — so the new shares, held by `new_pub_keys` under
+    /// `new_threshold`, back the very same master public key.
+    pub fn new_reshare(
+        our_uid: &NodeUid,
+        sec_key: SecretKey,
+        old_pub_key_set: &PublicKeySet,
+        old_sk_share: Option<&SecretKeyShare>,
+        old_indices: BTreeMap<NodeUid, u64>,
+        new_pub_keys: BTreeMap<NodeUid, PublicKey>,
+        new_threshold: usize,
+    ) -> (SyncKeyGen<NodeUid>, Option<SignedMsg<Propose>>) {
+        let our_idx = new_pub_keys
+            .keys()
+            .position(|uid| uid == our_uid)
+            .map(|idx| idx as u64);
+        let mut key_gen = SyncKeyGen {
+            our_idx,
+            sec_key,
+            pub_keys: new_pub_keys,
+            proposals: BTreeMap::new(),
+            threshold: new_threshold,
+            our_proposal: None,
+            disqualified: BTreeSet::new(),
+            quorum: old_pub_key_set.threshold(),
+            old_pub_key_set: Some(old_pub_key_set.clone()),
+            old_indices: Some(old_indices),
+        };
+        let (our_idx, old_sk_share) = match (our_idx, old_sk_share) {
+            (Some(our_idx), Some(old_sk_share)) => (our_idx, old_sk_share),
+            _ => return (key_gen, None), // Observer, or joining without an old share.
+        };
+        let mut rng = OsRng::new().expect("OS random number generator");
+        let secret = old_sk_share.reveal();
+        let our_proposal = BivarPoly::random_with_secret(new_threshold, secret, &mut rng);
+        let commit = our_proposal.commitment();
+        let encrypt = |(i, pk): (usize, &PublicKey)| {
+            let row = our_proposal.row(i as u64 + 1);
+            let bytes = bincode::serialize(&row).expect("failed to serialize row");
+            pk.encrypt(&bytes)
+        };
+        let rows: Vec<_> = key_gen.pub_keys.values().enumerate().map(encrypt).collect();
+        let propose = SignedMsg::new(our_idx, Propose(commit, rows), &key_gen.sec_key);
+        key_gen.our_proposal = Some(our_proposal);
+        (key_gen, Some(propose))
     }
 
-    /// Handles a `Propose` message. If it is valid, returns an `Accept` message to be broadcast.
+    /// Handles a signed `Propose` message. If it is valid, returns a signed `Accept` message to be
+    /// broadcast.
+    ///
+    /// The message authenticates itself: the claimed sender is read from `signed_propose`, not
+    /// from the transport that delivered it, and its signature is verified against that sender's
+    /// public key. This is what allows the message to be safely relayed by an untrusted gossip
+    /// network instead of being committed to an external total order first.
     ///
     /// If we are only an observer, `None` is returned instead and no messages need to be sent.
     pub fn handle_propose(
         &mut self,
-        sender_id: &NodeUid,
-        Propose(commit, rows): Propose,
+        signed_propose: SignedMsg<Propose>,
     ) -> Option<ProposeOutcome<NodeUid>> {
-        let sender_idx = self.node_index(sender_id)?;
+        let sender_idx = signed_propose.sender_idx;
+        let sender_id = self.node_id_by_idx(sender_idx)?.clone();
+        let pub_key = self.pub_keys.get(&sender_id)?.clone();
+        if !signed_propose.is_valid(&pub_key) {
+            let fault_log = FaultLog::init(sender_id, FaultKind::UnauthenticatedMessage);
+            return Some(ProposeOutcome::Invalid(fault_log));
+        }
+        let Propose(commit, rows) = signed_propose.payload;
+        if let Some(ref old_pub_key_set) = self.old_pub_key_set {
+            // This is a resharing: the proposer must commit to its own old secret key share,
+            // under its *old* index — which, unlike in a fresh key generation, is not generally
+            // the same as `sender_idx` (the proposer's index in the new validator set).
+            let old_idx = match self.old_indices.as_ref().and_then(|map| map.get(&sender_id)) {
+                Some(&old_idx) => old_idx,
+                None => {
+                    // Not a recognized old share holder: it cannot be resharing anything.
+                    let fault_log = FaultLog::init(sender_id, FaultKind::InvalidReshareCommitment);
+                    return Some(ProposeOutcome::Invalid(fault_log));
+                }
+            };
+            let old_share = old_pub_key_set.public_key_share(old_idx);
+            if PublicKeyShare::from(commit.evaluate(0, 0)) != old_share {
+                let fault_log = FaultLog::init(sender_id, FaultKind::InvalidReshareCommitment);
+                return Some(ProposeOutcome::Invalid(fault_log));
+            }
+        }
         let opt_commit_row = self.our_idx.map(|idx| commit.row(idx + 1));
         match self.proposals.entry(sender_idx) {
             Entry::Occupied(_) => return None, // Ignore multiple proposals.
             Entry::Vacant(entry) => {
-                entry.insert(ProposalState::new(commit));
+                entry.insert(ProposalState::new(commit, rows.clone()));
             }
         }
         // If we are only an observer, return `None`. We don't need to send `Accept`.
         let our_idx = self.our_idx?;
         let commit_row = opt_commit_row?;
-        let ser_row = self.sec_key.decrypt(rows.get(our_idx as usize)?)?;
+        let our_ciphertext = rows.get(our_idx as usize)?.clone();
+        let (ser_row, proof) = self.sec_key.decrypt_with_proof(&our_ciphertext)?;
         let row: Poly = if let Ok(row) = bincode::deserialize(&ser_row) {
             row
         } else {
             // Log the faulty node and ignore invalid messages.
-            let fault_log = FaultLog::init(sender_id.clone(), FaultKind::InvalidProposeMessage);
+            let fault_log = FaultLog::init(sender_id, FaultKind::InvalidProposeMessage);
             return Some(ProposeOutcome::Invalid(fault_log));
         };
         if row.commitment() != commit_row {
-            debug!("Invalid proposal from node {}.", sender_idx);
-            let fault_log = FaultLog::init(sender_id.clone(), FaultKind::InvalidProposeMessage);
-            return Some(ProposeOutcome::Invalid(fault_log));
+            debug!("Invalid row from node {}: broadcasting a complaint.", sender_idx);
+            let complaint = Complaint {
+                proposer_idx: sender_idx,
+                complainant_idx: our_idx,
+                ciphertext: our_ciphertext,
+                row,
+                proof,
+            };
+            return Some(ProposeOutcome::Complaint(complaint));
         }
         // The row is valid: now encrypt one value for each node.
         let encrypt = |(idx, pk): (usize, &PublicKey)| {
@@ -331,40 +594,70 @@ impl<NodeUid: Ord + Clone + Debug> SyncKeyGen<NodeUid> {
             pk.encrypt(ser_val)
         };
         let values = self.pub_keys.values().enumerate().map(encrypt).collect();
-        Some(ProposeOutcome::Valid(Accept(sender_idx, values)))
+        let accept = SignedMsg::new(our_idx, Accept(sender_idx, values), &self.sec_key);
+        Some(ProposeOutcome::Valid(accept))
     }
 
-    /// Handles an `Accept` message.
-    pub fn handle_accept(&mut self, sender_id: &NodeUid, accept: Accept) -> FaultLog<NodeUid> {
+    /// Handles a signed `Accept` message. As with `handle_propose`, the sender is read from the
+    /// message itself and its signature is verified, so a relayed message is still authenticated.
+    pub fn handle_accept(&mut self, signed_accept: SignedMsg<Accept>) -> FaultLog<NodeUid> {
         let mut fault_log = FaultLog::new();
-        if let Some(sender_idx) = self.node_index(sender_id) {
-            if let Err(err) = self.handle_accept_or_err(sender_idx, accept) {
-                debug!("Invalid accept from node {}: {}", sender_idx, err);
-                fault_log.append(sender_id.clone(), FaultKind::InvalidAcceptMessage);
-            }
+        let sender_idx = signed_accept.sender_idx;
+        let sender_id = match self.node_id_by_idx(sender_idx) {
+            Some(sender_id) => sender_id.clone(),
+            None => return fault_log,
+        };
+        let pub_key = match self.pub_keys.get(&sender_id) {
+            Some(pub_key) => pub_key.clone(),
+            None => return fault_log,
+        };
+        if !signed_accept.is_valid(&pub_key) {
+            fault_log.append(sender_id, FaultKind::UnauthenticatedMessage);
+            return fault_log;
+        }
+        if let Err(err) = self.handle_accept_or_err(sender_idx, signed_accept.payload) {
+            debug!("Invalid accept from node {}: {}", sender_idx, err);
+            fault_log.append(sender_id, FaultKind::InvalidAcceptMessage);
         }
         fault_log
     }
 
-    /// Returns the number of complete proposals. If this is at least `threshold + 1`, the keys can
+    /// Returns the number of complete, non-disqualified proposals. If this is at least
+    /// `threshold + 1` (when resharing, the old key's threshold + 1; see `is_ready`), the keys can
     /// be generated, but it is possible to wait for more to increase security.
     pub fn count_complete(&self) -> usize {
         self.proposals
-            .values()
-            .filter(|proposal| proposal.is_complete(self.threshold))
+            .iter()
+            .filter(|(idx, proposal)| {
+                !self.disqualified.contains(idx) && proposal.is_complete(self.threshold)
+            })
             .count()
     }
 
-    /// Returns `true` if the proposal of the given node is complete.
+    /// Returns `true` if the proposal of the given node is complete and not disqualified.
     pub fn is_node_ready(&self, proposer_id: &NodeUid) -> bool {
-        self.node_index(proposer_id)
-            .and_then(|proposer_idx| self.proposals.get(&proposer_idx))
-            .map_or(false, |proposal| proposal.is_complete(self.threshold))
+        let proposer_idx = match self.node_index(proposer_id) {
+            Some(proposer_idx) => proposer_idx,
+            None => return false,
+        };
+        !self.disqualified.contains(&proposer_idx)
+            && self
+                .proposals
+                .get(&proposer_idx)
+                .map_or(false, |proposal| proposal.is_complete(self.threshold))
     }
 
-    /// Returns `true` if enough proposals are complete to safely generate the new key.
+    /// Returns `true` if enough proposals are complete to safely generate the new key: more than
+    /// `threshold` for an ordinary key generation, or more than the old key's threshold when
+    /// resharing (see `new_reshare`).
     pub fn is_ready(&self) -> bool {
-        self.count_complete() > self.threshold
+        self.count_complete() > self.quorum
+    }
+
+    /// Returns `true` if a verified `Complaint` has disqualified the given proposer.
+    pub fn is_disqualified(&self, proposer_id: &NodeUid) -> bool {
+        self.node_index(proposer_id)
+            .map_or(false, |idx| self.disqualified.contains(&idx))
     }
 
     /// Returns the new secret key share and the public key set.
@@ -374,10 +667,54 @@ impl<NodeUid: Ord + Clone + Debug> SyncKeyGen<NodeUid> {
     ///
     /// If we are only an observer node, no secret key share is returned.
     pub fn generate(&self) -> (PublicKeySet, Option<SecretKeyShare>) {
+        let complete_idxs: BTreeSet<u64> = self
+            .proposals
+            .iter()
+            .filter(|(idx, proposal)| {
+                !self.disqualified.contains(idx) && proposal.is_complete(self.threshold)
+            })
+            .map(|(&idx, _)| idx)
+            .collect();
+        self.generate_for(&complete_idxs)
+    }
+
+    /// Like `generate`, but restricted to the given set of proposer indices, which are assumed to
+    /// already be complete. Used once nodes have agreed out-of-band (see `DkgState`) on the exact
+    /// set of proposals to use, instead of each independently using whichever proposals happen to
+    /// be locally complete.
+    ///
+    /// Proposers that we have disqualified are excluded even if `proposer_idxs` names them: an
+    /// out-of-band agreement may have been reached before a `Complaint` against one of its members
+    /// finished propagating everywhere, and folding a confirmed-faulty proposer's contribution into
+    /// the key regardless would defeat the entire point of disqualifying it.
+    pub(crate) fn generate_for(
+        &self,
+        proposer_idxs: &BTreeSet<u64>,
+    ) -> (PublicKeySet, Option<SecretKeyShare>) {
+        let proposer_idxs: BTreeSet<u64> = proposer_idxs
+            .iter()
+            .cloned()
+            .filter(|idx| !self.disqualified.contains(idx))
+            .collect();
+        match &self.old_indices {
+            Some(old_indices) => self.generate_reshare_for(&proposer_idxs, old_indices),
+            None => self.generate_fresh_for(&proposer_idxs),
+        }
+    }
+
+    /// `generate_for` for an ordinary, non-resharing key generation: every proposer contributes
+    /// an independent random polynomial, so the master secret is simply their sum.
+    fn generate_fresh_for(
+        &self,
+        proposer_idxs: &BTreeSet<u64>,
+    ) -> (PublicKeySet, Option<SecretKeyShare>) {
         let mut pk_commit = Poly::zero().commitment();
         let mut opt_sk_val = self.our_idx.map(|_| Fr::zero());
-        let is_complete = |proposal: &&ProposalState| proposal.is_complete(self.threshold);
-        for proposal in self.proposals.values().filter(is_complete) {
+        for proposer_idx in proposer_idxs {
+            let proposal = match self.proposals.get(proposer_idx) {
+                Some(proposal) => proposal,
+                None => continue,
+            };
             pk_commit += proposal.commit.row(0);
             if let Some(sk_val) = opt_sk_val.as_mut() {
                 let row: Poly = Poly::interpolate(proposal.values.iter().take(self.threshold + 1));
@@ -388,6 +725,206 @@ impl<NodeUid: Ord + Clone + Debug> SyncKeyGen<NodeUid> {
         (pk_commit.into(), opt_sk)
     }
 
+    /// `generate_for` for a resharing: unlike a fresh key generation, each proposer's constant
+    /// term is itself an old secret key share rather than an independent random value, so a plain
+    /// sum would reconstruct an arbitrary value, not the original master secret. Weighting each
+    /// contribution by its Lagrange coefficient at `x = 0` over the old indices actually used
+    /// reconstructs the original secret instead, exactly as plain Shamir reconstruction would from
+    /// those same old shares.
+    fn generate_reshare_for(
+        &self,
+        proposer_idxs: &BTreeSet<u64>,
+        old_indices: &BTreeMap<NodeUid, u64>,
+    ) -> (PublicKeySet, Option<SecretKeyShare>) {
+        let old_idx_of = |proposer_idx: &u64| -> Option<u64> {
+            let proposer_id = self.node_id_by_idx(*proposer_idx)?;
+            old_indices.get(proposer_id).map(|idx| idx + 1)
+        };
+        let old_idxs: Vec<u64> = proposer_idxs.iter().filter_map(old_idx_of).collect();
+        let mut pk_commit = Poly::zero().commitment();
+        let mut opt_sk_val = self.our_idx.map(|_| Fr::zero());
+        for proposer_idx in proposer_idxs {
+            let proposal = match self.proposals.get(proposer_idx) {
+                Some(proposal) => proposal,
+                None => continue,
+            };
+            let old_idx = match old_idx_of(proposer_idx) {
+                Some(old_idx) => old_idx,
+                None => continue,
+            };
+            let coeff = Self::lagrange_coefficient(old_idx, &old_idxs);
+            pk_commit += proposal.commit.row(0) * coeff;
+            if let Some(sk_val) = opt_sk_val.as_mut() {
+                let row: Poly = Poly::interpolate(proposal.values.iter().take(self.threshold + 1));
+                let mut contribution = row.evaluate(0);
+                contribution.mul_assign(&coeff);
+                sk_val.add_assign(&contribution);
+            }
+        }
+        let opt_sk = opt_sk_val.map(SecretKeyShare::from_value);
+        (pk_commit.into(), opt_sk)
+    }
+
+    /// Returns the Lagrange coefficient at `x = 0` for `x_i`, interpolated against the full set of
+    /// points `xs` (which includes `x_i` itself). Computed by interpolating the indicator
+    /// function that is `1` at `x_i` and `0` at every other point in `xs`, which by definition
+    /// evaluates at `0` to exactly that Lagrange basis coefficient.
+    fn lagrange_coefficient(x_i: u64, xs: &[u64]) -> Fr {
+        let points: BTreeMap<u64, Fr> = xs
+            .iter()
+            .map(|&x| (x, if x == x_i { Fr::one() } else { Fr::zero() }))
+            .collect();
+        Poly::interpolate(points.iter()).evaluate(0)
+    }
+
+    /// Returns `true` if we locally have everything `generate_for` needs to include
+    /// `proposer_idx` in the result: the `Propose` itself, plus, unless we are an observer, enough
+    /// valid `Accept` values to interpolate its row. A node that is merely behind on gossip may have agreed that a
+    /// proposer's entry is complete (via `AllAcks`) before reaching this point itself; callers
+    /// must wait for this to become `true` for every agreed proposer before calling
+    /// `generate_for`, or they will silently omit that proposer's contribution.
+    pub(crate) fn has_generate_data(&self, proposer_idx: u64) -> bool {
+        self.proposals
+            .get(&proposer_idx)
+            .map_or(false, |proposal| {
+                self.our_idx.is_none() || proposal.values.len() > self.threshold
+            })
+    }
+
+    /// Returns, for each locally complete, non-disqualified proposal, the set of node indices
+    /// whose `Accept` contributed to it. Used by `DkgState` to build an `AllAcks` message.
+    pub(crate) fn complete_accepts(&self) -> BTreeMap<u64, BTreeSet<u64>> {
+        self.proposals
+            .iter()
+            .filter(|(idx, proposal)| {
+                !self.disqualified.contains(idx) && proposal.is_complete(self.threshold)
+            })
+            .map(|(&idx, proposal)| (idx, proposal.accepts.clone()))
+            .collect()
+    }
+
+    /// Handles a `Complaint` about `complaint.proposer_idx`'s row towards
+    /// `complaint.complainant_idx`. If the complaint is verified to be justified, the proposer is
+    /// disqualified and excluded from `generate`. Otherwise, the complaint itself was bogus and
+    /// the complainant is reported instead.
+    ///
+    /// Returns, alongside the fault log, whether `ciphertext`/`proof` actually proved that `row`
+    /// is what the proposer sent. This is `false` for a complaint with a forged or mismatched
+    /// proof, and callers must not treat that case as grounds to call `justify`: a degree-`t`
+    /// polynomial's secret only stays hidden as long as at most `t` of its rows are ever revealed,
+    /// so responding to unverified complaints would let anyone harvest enough rows, one bogus
+    /// complaint at a time, to reconstruct the proposer's entire contribution.
+    pub fn handle_complaint(&mut self, complaint: Complaint) -> (FaultLog<NodeUid>, bool) {
+        let mut fault_log = FaultLog::new();
+        let Complaint {
+            proposer_idx,
+            complainant_idx,
+            ciphertext,
+            row,
+            proof,
+        } = complaint;
+        let proposer_id = match self.node_id_by_idx(proposer_idx) {
+            Some(proposer_id) => proposer_id.clone(),
+            None => return (fault_log, false),
+        };
+        let complainant_id = match self.node_id_by_idx(complainant_idx) {
+            Some(complainant_id) => complainant_id.clone(),
+            None => return (fault_log, false),
+        };
+        let complainant_pub_key = match self.pub_keys.get(&complainant_id) {
+            Some(pub_key) => pub_key.clone(),
+            None => return (fault_log, false),
+        };
+        let proposal = match self.proposals.get(&proposer_idx) {
+            Some(proposal) => proposal,
+            None => return (fault_log, false),
+        };
+        let is_same_ciphertext = proposal
+            .rows
+            .get(complainant_idx as usize)
+            .map_or(false, |sent| *sent == ciphertext);
+        let ser_row = bincode::serialize(&row).ok();
+        let is_valid_proof = ser_row
+            .as_ref()
+            .map_or(false, |ser| ciphertext.verify_decryption(ser, &proof, &complainant_pub_key));
+        if !is_same_ciphertext || !is_valid_proof {
+            fault_log.append(complainant_id, FaultKind::InvalidComplaint);
+            return (fault_log, false);
+        }
+        if row.commitment() == proposal.commit.row(complainant_idx + 1) {
+            // The row was fine after all: the complaint itself was unjustified.
+            fault_log.append(complainant_id, FaultKind::UnjustifiedComplaint);
+        } else {
+            // The proof shows the proposer really did send an invalid row.
+            self.disqualified.insert(proposer_idx);
+            fault_log.append(proposer_id, FaultKind::InvalidRow);
+        }
+        (fault_log, true)
+    }
+
+    /// Handles a signed `Justification`, in which a proposer reveals the plaintext row it sent to
+    /// `justification.complainant_idx`, in response to a `Complaint`. A row that doesn't match the
+    /// public commitment disqualifies the proposer. A matching row does not lift a
+    /// disqualification established by a verified `Complaint`: that complaint's proof already
+    /// shows what the proposer actually sent, and the proposer can always reveal a correct row.
+    ///
+    /// Disqualification is irreversible reputational damage, so — like `Propose` and `Accept` —
+    /// the message authenticates itself: the claimed proposer is read from `signed_justification`
+    /// and its signature is verified, so nobody else can forge a bogus row and get an honest
+    /// proposer disqualified.
+    pub fn handle_justification(
+        &mut self,
+        signed_justification: SignedMsg<Justification>,
+    ) -> FaultLog<NodeUid> {
+        let mut fault_log = FaultLog::new();
+        let sender_idx = signed_justification.sender_idx;
+        let sender_id = match self.node_id_by_idx(sender_idx) {
+            Some(sender_id) => sender_id.clone(),
+            None => return fault_log,
+        };
+        let pub_key = match self.pub_keys.get(&sender_id) {
+            Some(pub_key) => pub_key.clone(),
+            None => return fault_log,
+        };
+        if !signed_justification.is_valid(&pub_key) {
+            fault_log.append(sender_id, FaultKind::UnauthenticatedMessage);
+            return fault_log;
+        }
+        let Justification {
+            proposer_idx,
+            complainant_idx,
+            row,
+        } = signed_justification.payload;
+        if proposer_idx != sender_idx {
+            // The signer is vouching for someone else's row: that doesn't justify anything.
+            fault_log.append(sender_id, FaultKind::UnauthenticatedMessage);
+            return fault_log;
+        }
+        let proposal = match self.proposals.get(&proposer_idx) {
+            Some(proposal) => proposal,
+            None => return fault_log,
+        };
+        if row.commitment() != proposal.commit.row(complainant_idx + 1) {
+            self.disqualified.insert(proposer_idx);
+            fault_log.append(sender_id, FaultKind::InvalidRow);
+        }
+        fault_log
+    }
+
+    /// If we are a proposer, returns the signed `Justification` revealing the row we sent to
+    /// `complainant_idx`, so other nodes can verify whether a `Complaint` against us is justified.
+    /// Returns `None` if we are an observer and proposed nothing.
+    pub fn justify(&self, complainant_idx: u64) -> Option<SignedMsg<Justification>> {
+        let proposer_idx = self.our_idx?;
+        let our_proposal = self.our_proposal.as_ref()?;
+        let justification = Justification {
+            proposer_idx,
+            complainant_idx,
+            row: our_proposal.row(complainant_idx + 1),
+        };
+        Some(SignedMsg::new(proposer_idx, justification, &self.sec_key))
+    }
+
     /// Handles an `Accept` message or returns an error string.
     fn handle_accept_or_err(
         &mut self,
@@ -431,4 +968,520 @@ impl<NodeUid: Ord + Clone + Debug> SyncKeyGen<NodeUid> {
             None
         }
     }
+
+    /// Returns the node with the given index, or `None` if it is out of range.
+    fn node_id_by_idx(&self, node_idx: u64) -> Option<&NodeUid> {
+        self.pub_keys.keys().nth(node_idx as usize)
+    }
+}
+
+/// A message of the gossip-based key generation agreement, to be broadcast to all participants.
+/// `Propose`, `Accept` and `Justification` carry the sender's signature and authenticate
+/// themselves, so they can be safely relayed through the gossip network; only `AllAcks` relies on
+/// the transport's own idea of who sent it. See `DkgState`.
+#[derive(Deserialize, Serialize, Clone, Hash, Eq, PartialEq, Debug)]
+pub enum Message {
+    /// See `SyncKeyGen::handle_propose`.
+    Propose(SignedMsg<Propose>),
+    /// See `SyncKeyGen::handle_accept`.
+    Accept(SignedMsg<Accept>),
+    /// An endorsement of a specific set of complete proposals. See `AllAcks`.
+    AllAcks(AllAcks),
+    /// A provable accusation that a proposer's row was invalid. See `SyncKeyGen::handle_complaint`.
+    Complaint(Complaint),
+    /// A proposer's response to a `Complaint`. See `SyncKeyGen::handle_justification`.
+    Justification(SignedMsg<Justification>),
+}
+
+/// A node's endorsement of the exact set of proposals and accepts it used to determine that a
+/// proposal is complete. Once `2 * threshold + 1` nodes broadcast the same `AllAcks`, every node
+/// that received it adopts the lexicographically-first such set and generates the key restricted
+/// to it, so that all honest nodes end up agreeing on identical key shares.
+#[derive(Deserialize, Serialize, Clone, Hash, Eq, PartialEq, PartialOrd, Ord, Debug)]
+pub struct AllAcks {
+    /// The indices of the proposers whose proposals we consider complete.
+    proposers: BTreeSet<u64>,
+    /// The exact `(proposer_idx, acceptor_idx)` pairs that were counted towards completeness.
+    acks: BTreeSet<(u64, u64)>,
+}
+
+/// The result of a completed gossip-based distributed key generation.
+pub struct Outcome {
+    /// The public key set, known to all nodes.
+    pub pub_key_set: PublicKeySet,
+    /// Our secret key share, or `None` if we are only an observer.
+    pub opt_sk_share: Option<SecretKeyShare>,
+}
+
+/// A gossip-based wrapper around `SyncKeyGen` that reaches agreement on the canonical set of
+/// `Propose`/`Accept` messages by itself, so a plain authenticated point-to-point network is
+/// enough: unlike `SyncKeyGen` alone, nodes do not need to handle the same messages in the same
+/// order, e.g. via an external total order like a blockchain.
+pub struct DkgState<NodeUid> {
+    key_gen: SyncKeyGen<NodeUid>,
+    our_uid: NodeUid,
+    threshold: usize,
+    /// Whether we have already broadcast our own `AllAcks`.
+    sent_all_acks: bool,
+    /// The `AllAcks` we have received so far, by sender.
+    all_acks: BTreeMap<NodeUid, AllAcks>,
+    /// The `AllAcks` set that has been agreed upon, if any.
+    agreed: Option<AllAcks>,
+    /// Messages waiting to be broadcast.
+    outgoing: VecDeque<Message>,
+    /// The result of the key generation, once `agreed` has been determined.
+    outcome: Option<Outcome>,
+}
+
+impl<NodeUid: Ord + Clone + Debug> DkgState<NodeUid> {
+    /// Creates a new gossip-based key generation instance. Our initial `Propose` message, which
+    /// must be broadcast to all participants, is queued for `poll_output`; if we are only an
+    /// observer, nothing is queued.
+    pub fn new(
+        our_uid: &NodeUid,
+        sec_key: SecretKey,
+        pub_keys: BTreeMap<NodeUid, PublicKey>,
+        threshold: usize,
+    ) -> DkgState<NodeUid> {
+        let (key_gen, opt_propose) = SyncKeyGen::new(our_uid, sec_key, pub_keys, threshold);
+        let mut outgoing = VecDeque::new();
+        if let Some(propose) = opt_propose {
+            outgoing.push_back(Message::Propose(propose));
+        }
+        DkgState {
+            key_gen,
+            our_uid: our_uid.clone(),
+            threshold,
+            sent_all_acks: false,
+            all_acks: BTreeMap::new(),
+            agreed: None,
+            outgoing,
+            outcome: None,
+        }
+    }
+
+    /// Handles a message received from `sender_id`. `Propose` and `Accept` authenticate
+    /// themselves via their signature, so `sender_id` — the node that directly delivered the
+    /// message to us, which may just be relaying it — is only used to attribute `AllAcks`.
+    pub fn handle_message(&mut self, sender_id: &NodeUid, message: Message) -> FaultLog<NodeUid> {
+        match message {
+            Message::Propose(propose) => self.handle_propose(propose),
+            Message::Accept(accept) => self.handle_accept(accept),
+            Message::AllAcks(all_acks) => self.handle_all_acks(sender_id, all_acks),
+            Message::Complaint(complaint) => self.handle_complaint(complaint),
+            Message::Justification(justification) => {
+                self.key_gen.handle_justification(justification)
+            }
+        }
+    }
+
+    /// Returns the next message that needs to be broadcast to all nodes, if any.
+    pub fn poll_output(&mut self) -> Option<Message> {
+        self.outgoing.pop_front()
+    }
+
+    /// Returns the result of the key generation, once it has completed.
+    pub fn outcome(&self) -> Option<&Outcome> {
+        self.outcome.as_ref()
+    }
+
+    fn handle_propose(&mut self, propose: SignedMsg<Propose>) -> FaultLog<NodeUid> {
+        let fault_log = match self.key_gen.handle_propose(propose) {
+            Some(ProposeOutcome::Valid(accept)) => {
+                self.outgoing.push_back(Message::Accept(accept));
+                FaultLog::new()
+            }
+            Some(ProposeOutcome::Complaint(complaint)) => {
+                self.outgoing.push_back(Message::Complaint(complaint));
+                FaultLog::new()
+            }
+            Some(ProposeOutcome::Invalid(fault_log)) => fault_log,
+            None => FaultLog::new(),
+        };
+        // We may have just caught up on a proposal that the network already agreed to include.
+        self.try_generate();
+        fault_log
+    }
+
+    fn handle_accept(&mut self, accept: SignedMsg<Accept>) -> FaultLog<NodeUid> {
+        let fault_log = self.key_gen.handle_accept(accept);
+        self.broadcast_all_acks_if_ready();
+        // We may have just caught up on the last `Accept` the agreed-upon set was waiting for.
+        self.try_generate();
+        fault_log
+    }
+
+    /// Handles a `Complaint`, and if we are the accused proposer and the complaint was verified,
+    /// broadcasts a `Justification` in response. An unverified complaint — one whose ciphertext or
+    /// decryption proof doesn't actually hold up — is never responded to, or anyone could force us
+    /// to reveal our rows one bogus complaint at a time.
+    fn handle_complaint(&mut self, complaint: Complaint) -> FaultLog<NodeUid> {
+        let complainant_idx = complaint.complainant_idx;
+        let proposer_idx = complaint.proposer_idx;
+        let (fault_log, verified) = self.key_gen.handle_complaint(complaint);
+        if verified {
+            if let Some(justification) = self.key_gen.justify(complainant_idx) {
+                if justification.payload.proposer_idx == proposer_idx {
+                    self.outgoing.push_back(Message::Justification(justification));
+                }
+            }
+        }
+        fault_log
+    }
+
+    fn handle_all_acks(&mut self, sender_id: &NodeUid, all_acks: AllAcks) -> FaultLog<NodeUid> {
+        if self.agreed.is_none() {
+            self.all_acks.insert(sender_id.clone(), all_acks);
+            self.try_agree();
+        }
+        FaultLog::new()
+    }
+
+    /// Broadcasts our own `AllAcks` as soon as more than `threshold` proposals are locally
+    /// complete.
+    fn broadcast_all_acks_if_ready(&mut self) {
+        if self.sent_all_acks || self.key_gen.count_complete() <= self.threshold {
+            return;
+        }
+        self.sent_all_acks = true;
+        let all_acks = self.our_all_acks();
+        self.outgoing.push_back(Message::AllAcks(all_acks.clone()));
+        self.all_acks.insert(self.our_uid.clone(), all_acks);
+        self.try_agree();
+    }
+
+    /// Returns our endorsement of the currently complete proposals.
+    fn our_all_acks(&self) -> AllAcks {
+        let accepts = self.key_gen.complete_accepts();
+        let proposers = accepts.keys().cloned().collect();
+        let acks = accepts
+            .into_iter()
+            .flat_map(|(proposer_idx, acceptors)| {
+                acceptors
+                    .into_iter()
+                    .map(move |acceptor_idx| (proposer_idx, acceptor_idx))
+            })
+            .collect();
+        AllAcks { proposers, acks }
+    }
+
+    /// Adopts the lexicographically-first `AllAcks` that at least `2 * threshold + 1` nodes have
+    /// endorsed. This fixes *which* set of proposals to use; it does not by itself generate the
+    /// key, since we may not yet locally have everything that set needs (see `try_generate`).
+    fn try_agree(&mut self) {
+        if self.agreed.is_none() {
+            let mut counts: BTreeMap<&AllAcks, usize> = BTreeMap::new();
+            for all_acks in self.all_acks.values() {
+                *counts.entry(all_acks).or_insert(0) += 1;
+            }
+            let agreed = counts
+                .into_iter()
+                .filter(|(_, count)| *count > 2 * self.threshold)
+                .map(|(all_acks, _)| all_acks.clone())
+                .min();
+            self.agreed = agreed;
+        }
+        self.try_generate();
+    }
+
+    /// Generates the key restricted to the agreed-upon set of proposals, once we locally have
+    /// everything that set needs. A node that is only behind on gossip, not faulty, may reach
+    /// agreement on a set before it has handled enough `Propose`/`Accept` messages itself; calling
+    /// `generate_for` too early would silently omit a proposer's contribution and produce a key
+    /// that doesn't match the other nodes'. This is retried every time we handle a message that
+    /// could have filled the gap, so we generate as soon as we are able to, without ever doing so
+    /// prematurely.
+    fn try_generate(&mut self) {
+        if self.outcome.is_some() {
+            return;
+        }
+        let all_acks = match &self.agreed {
+            Some(all_acks) => all_acks,
+            None => return,
+        };
+        if !all_acks
+            .proposers
+            .iter()
+            .all(|&idx| self.key_gen.has_generate_data(idx))
+        {
+            return;
+        }
+        let (pub_key_set, opt_sk_share) = self.key_gen.generate_for(&all_acks.proposers);
+        self.outcome = Some(Outcome {
+            pub_key_set,
+            opt_sk_share,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Floods every pending outgoing message to every node, round by round, until nobody has
+    /// anything left to send. Mirrors an unordered gossip network: who handles what first doesn't
+    /// matter, only that everyone eventually handles everything.
+    fn run_dkg_to_completion(nodes: &mut BTreeMap<usize, DkgState<usize>>) {
+        loop {
+            let mut outgoing = Vec::new();
+            for (&id, node) in nodes.iter_mut() {
+                while let Some(message) = node.poll_output() {
+                    outgoing.push((id, message));
+                }
+            }
+            if outgoing.is_empty() {
+                break;
+            }
+            for (sender, message) in outgoing {
+                for node in nodes.values_mut() {
+                    let _ = node.handle_message(&sender, message.clone());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn dkg_state_reaches_agreement_without_a_total_order() {
+        let (threshold, node_num) = (1, 4);
+        let sec_keys: Vec<SecretKey> = (0..node_num).map(|_| rand::random()).collect();
+        let pub_keys: BTreeMap<usize, PublicKey> = sec_keys
+            .iter()
+            .map(SecretKey::public_key)
+            .enumerate()
+            .collect();
+        let mut nodes: BTreeMap<usize, DkgState<usize>> = BTreeMap::new();
+        for (id, sec_key) in sec_keys.into_iter().enumerate() {
+            nodes.insert(id, DkgState::new(&id, sec_key, pub_keys.clone(), threshold));
+        }
+        run_dkg_to_completion(&mut nodes);
+
+        let pub_key_set = nodes[&0]
+            .outcome()
+            .expect("node 0 completed the DKG")
+            .pub_key_set
+            .clone();
+        let mut sig_shares = BTreeMap::new();
+        for (&id, node) in &nodes {
+            let outcome = node.outcome().expect("every node completed the DKG");
+            assert_eq!(outcome.pub_key_set, pub_key_set, "all nodes must agree on the same key");
+            let sks = outcome.opt_sk_share.as_ref().expect("no observers in this test");
+            sig_shares.insert(id as u64, sks.sign("gossip works"));
+        }
+        let some_shares: BTreeMap<u64, _> = sig_shares.into_iter().take(threshold + 1).collect();
+        let sig = pub_key_set
+            .combine_signatures(&some_shares)
+            .expect("threshold + 1 shares combine into a signature");
+        assert!(pub_key_set.public_key().verify(&sig, "gossip works"));
+    }
+
+    #[test]
+    fn handle_propose_rejects_a_tampered_signature() {
+        let sec_key0: SecretKey = rand::random();
+        let sec_key1: SecretKey = rand::random();
+        let mut pub_keys = BTreeMap::new();
+        pub_keys.insert(0usize, sec_key0.public_key());
+        pub_keys.insert(1usize, sec_key1.public_key());
+
+        let (mut key_gen1, _) = SyncKeyGen::new(&1, sec_key1, pub_keys.clone(), 1);
+        let (_, opt_propose0) = SyncKeyGen::new(&0, sec_key0, pub_keys, 1);
+        let mut signed_propose = opt_propose0.expect("validator 0 produces a proposal");
+
+        // Tamper with the payload without re-signing: the signature no longer matches.
+        signed_propose.payload.1.truncate(1);
+
+        match key_gen1.handle_propose(signed_propose) {
+            Some(ProposeOutcome::Invalid(_)) => {}
+            other => panic!("expected the tampered message to be rejected, got_some={}", other.is_some()),
+        }
+    }
+
+    #[test]
+    fn complaint_then_justification_disqualifies_a_bad_proposer() {
+        let sec_key0: SecretKey = rand::random();
+        let sec_key1: SecretKey = rand::random();
+        let sec_key2: SecretKey = rand::random();
+        let mut pub_keys = BTreeMap::new();
+        pub_keys.insert(0usize, sec_key0.public_key());
+        pub_keys.insert(1usize, sec_key1.public_key());
+        pub_keys.insert(2usize, sec_key2.public_key());
+
+        let (mut key_gen0, opt_propose0) = SyncKeyGen::new(&0, sec_key0, pub_keys.clone(), 1);
+        let (mut key_gen1, _) = SyncKeyGen::new(&1, sec_key1, pub_keys.clone(), 1);
+        let (mut key_gen2, _) = SyncKeyGen::new(&2, sec_key2, pub_keys.clone(), 1);
+        let mut signed_propose0 = opt_propose0.expect("validator 0 produces a proposal");
+
+        // Corrupt only the row node 0 actually sends to node 1 (the complainant), then re-sign
+        // so the message still authenticates as coming from node 0.
+        let bad_row = key_gen0
+            .our_proposal
+            .as_ref()
+            .expect("validator 0 kept its own polynomial")
+            .row(99);
+        let bad_bytes = bincode::serialize(&bad_row).expect("serializes");
+        signed_propose0.payload.1[1] = pub_keys[&1].encrypt(&bad_bytes);
+        signed_propose0.sig = key_gen0
+            .sec_key
+            .sign(bincode::serialize(&signed_propose0.payload).expect("serializes"));
+
+        // Node 0's own row is untouched, so it still sends a normal `Accept`.
+        match key_gen0.handle_propose(signed_propose0.clone()) {
+            Some(ProposeOutcome::Valid(_)) => {}
+            other => panic!("node 0's own row is untouched, got_some={}", other.is_some()),
+        }
+        // Node 2 has no reason to complain either: its own row is untouched too.
+        match key_gen2.handle_propose(signed_propose0.clone()) {
+            Some(ProposeOutcome::Valid(_)) => {}
+            other => panic!("node 2's own row is untouched, got_some={}", other.is_some()),
+        }
+        // Node 1 detects that the row sent to it doesn't match the public commitment.
+        let complaint = match key_gen1.handle_propose(signed_propose0) {
+            Some(ProposeOutcome::Complaint(complaint)) => complaint,
+            other => panic!("expected node 1 to detect the bad row, got_some={}", other.is_some()),
+        };
+
+        // Node 2 can verify the complaint purely from public information and the proof.
+        let (_, verified) = key_gen2.handle_complaint(complaint.clone());
+        assert!(verified, "the proof matches the ciphertext node 0 actually sent");
+        assert!(key_gen2.is_disqualified(&0));
+
+        // Node 0 handles the complaint against itself too, so it can respond with a
+        // `Justification`.
+        let (_, verified) = key_gen0.handle_complaint(complaint);
+        assert!(verified);
+        let justification = key_gen0.justify(1).expect("the proposer can justify its own row");
+        assert_eq!(justification.payload.proposer_idx, 0);
+
+        // The justification reveals the correct row, but the verified complaint already proved
+        // that the row node 0 actually sent was bad, so it does not clear node 0.
+        key_gen2.handle_justification(justification);
+        assert!(
+            key_gen2.is_disqualified(&0),
+            "a justification must not clear a proposer disqualified by a verified complaint"
+        );
+
+        // A disqualified proposer must not contribute to the generated key, even if it is named
+        // in the requested set.
+        let mut requested = BTreeSet::new();
+        requested.insert(0u64);
+        let (pk_with_disqualified, _) = key_gen2.generate_for(&requested);
+        let (pk_without_it, _) = key_gen2.generate_for(&BTreeSet::new());
+        assert_eq!(pk_with_disqualified, pk_without_it);
+    }
+
+    #[test]
+    fn reshare_preserves_the_master_key_across_a_new_validator_set() {
+        let (old_threshold, old_node_num) = (1, 4);
+        let old_sec_keys: Vec<SecretKey> = (0..old_node_num).map(|_| rand::random()).collect();
+        let old_pub_keys: BTreeMap<usize, PublicKey> = old_sec_keys
+            .iter()
+            .map(SecretKey::public_key)
+            .enumerate()
+            .collect();
+
+        // Run an ordinary key generation for the old committee {0, 1, 2, 3}.
+        let mut old_nodes = BTreeMap::new();
+        let mut proposes = Vec::new();
+        for (id, sec_key) in old_sec_keys.into_iter().enumerate() {
+            let (key_gen, opt_propose) = SyncKeyGen::new(&id, sec_key, old_pub_keys.clone(), old_threshold);
+            old_nodes.insert(id, key_gen);
+            proposes.push(opt_propose.expect("every old validator proposes"));
+        }
+        let mut accepts = Vec::new();
+        for propose in proposes {
+            for (&id, node) in &mut old_nodes {
+                match node.handle_propose(propose.clone()) {
+                    Some(ProposeOutcome::Valid(accept)) => accepts.push(accept),
+                    other => panic!("expected a valid accept, got_some={}", other.is_some()),
+                }
+            }
+        }
+        for accept in accepts {
+            for node in old_nodes.values_mut() {
+                node.handle_accept(accept.clone());
+            }
+        }
+        let old_pub_key_set = old_nodes[&0].generate().0;
+        let mut old_shares = BTreeMap::new();
+        for (&id, node) in &old_nodes {
+            assert!(node.is_ready());
+            let (pks, opt_sks) = node.generate();
+            assert_eq!(pks, old_pub_key_set);
+            old_shares.insert(id, opt_sks.expect("every old node holds a share"));
+        }
+
+        // Reshare to a new committee {1, 2, 3, 4}: node 0 leaves, node 4 joins. Every continuing
+        // node's new index differs from its old one once node 0 drops out of the `BTreeMap`.
+        let old_indices: BTreeMap<usize, u64> =
+            [(1usize, 1u64), (2, 2), (3, 3)].iter().cloned().collect();
+        let mut new_pub_keys = BTreeMap::new();
+        for &id in &[1usize, 2, 3] {
+            new_pub_keys.insert(id, old_pub_keys[&id].clone());
+        }
+        let sec_key4: SecretKey = rand::random();
+        new_pub_keys.insert(4, sec_key4.public_key());
+        let new_threshold = 1;
+
+        let mut new_nodes = BTreeMap::new();
+        let mut reshare_proposes = Vec::new();
+        for &id in &[1usize, 2, 3] {
+            let old_key_gen = old_nodes.remove(&id).expect("old node still present");
+            let sec_key = old_key_gen.sec_key;
+            let (key_gen, opt_propose) = SyncKeyGen::new_reshare(
+                &id,
+                sec_key,
+                &old_pub_key_set,
+                Some(&old_shares[&id]),
+                old_indices.clone(),
+                new_pub_keys.clone(),
+                new_threshold,
+            );
+            new_nodes.insert(id, key_gen);
+            reshare_proposes.push(opt_propose.expect("every continuing node reshares its share"));
+        }
+        let (key_gen4, opt_propose4) = SyncKeyGen::new_reshare(
+            &4usize,
+            sec_key4,
+            &old_pub_key_set,
+            None,
+            old_indices,
+            new_pub_keys.clone(),
+            new_threshold,
+        );
+        assert!(opt_propose4.is_none(), "node 4 has no old share to reshare");
+        new_nodes.insert(4, key_gen4);
+
+        let mut reshare_accepts = Vec::new();
+        for propose in reshare_proposes {
+            for node in new_nodes.values_mut() {
+                match node.handle_propose(propose.clone()) {
+                    Some(ProposeOutcome::Valid(accept)) => reshare_accepts.push(accept),
+                    other => panic!("expected a valid reshare accept, got_some={}", other.is_some()),
+                }
+            }
+        }
+        for accept in reshare_accepts {
+            for node in new_nodes.values_mut() {
+                node.handle_accept(accept.clone());
+            }
+        }
+
+        // The master public key is unchanged, even though every continuing node's index shifted.
+        let new_pub_key_set = new_nodes[&1].generate().0;
+        assert_eq!(new_pub_key_set.public_key(), old_pub_key_set.public_key());
+
+        let msg = "reshare preserves the master key";
+        let mut sig_shares = BTreeMap::new();
+        for &id in &[1usize, 2] {
+            assert!(new_nodes[&id].is_ready());
+            let (pks, opt_sks) = new_nodes[&id].generate();
+            assert_eq!(pks.public_key(), old_pub_key_set.public_key());
+            let new_idx = new_pub_keys.keys().position(|&uid| uid == id).unwrap() as u64;
+            let sks = opt_sks.expect("continuing node holds a new share");
+            sig_shares.insert(new_idx, sks.sign(msg));
+        }
+        let sig = new_pub_key_set
+            .combine_signatures(&sig_shares)
+            .expect("threshold + 1 new shares combine into a signature");
+        assert!(old_pub_key_set.public_key().verify(&sig, msg));
+    }
 }